@@ -1,30 +1,149 @@
 use async_trait::async_trait;
-use futures::{stream::BoxStream, StreamExt};
+use futures::{future, stream::BoxStream, Stream, StreamExt};
+use rand::Rng;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::File,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
     result::Result,
     sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
 };
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration};
+use tokio_stream::{wrappers::WatchStream, StreamMap};
+use tokio_util::sync::CancellationToken;
 
 type City = String;
 type Temperature = u64;
 
+/// Optional HTTP front-end for a [`StreamCache`], gated behind the `server`
+/// feature so embedders that only need the in-process cache don't pull in
+/// axum.
+#[cfg(feature = "server")]
+pub mod server;
+
+/// Bumped whenever the on-disk snapshot layout changes; `restore` refuses to
+/// read a file stamped with a different version.
+const CACHE_VERSION: u32 = 1;
+
+/// Errors that can occur while reading or writing a cache snapshot.
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Codec(bincode::Error),
+    UnsupportedVersion { expected: u32, found: u32 },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "snapshot io error: {}", err),
+            SnapshotError::Codec(err) => write!(f, "snapshot codec error: {}", err),
+            SnapshotError::UnsupportedVersion { expected, found } => write!(
+                f,
+                "snapshot version mismatch: expected {}, found {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        SnapshotError::Io(err)
+    }
+}
+
+/// Tunables for the resilient background updater started by
+/// [`StreamCache::new`]/[`StreamCache::with_config`]: how often to
+/// reconcile drift with a full `fetch()`, and how aggressively to retry a
+/// dropped `subscribe()` stream.
+#[derive(Debug, Clone)]
+pub struct StreamCacheConfig {
+    pub refetch_interval: Duration,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for StreamCacheConfig {
+    fn default() -> Self {
+        Self {
+            refetch_interval: Duration::from_secs(5 * 60),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
 #[async_trait]
 pub trait Api: Send + Sync + 'static {
     async fn fetch(&self) -> Result<HashMap<City, Temperature>, String>;
     async fn subscribe(&self) -> BoxStream<Result<(City, Temperature), String>>;
 }
 
+#[derive(Debug)]
 pub struct StreamCache {
     results: Arc<Mutex<HashMap<String, u64>>>,
+    senders: Arc<Mutex<HashMap<City, watch::Sender<Temperature>>>>,
+    /// Bumped every time a city is seen for the first time, so a long-lived
+    /// `subscribe_all()` knows to pick up a new per-city watch stream
+    /// instead of only ever seeing the cities known at call time.
+    city_added: watch::Sender<u64>,
+    cancel: CancellationToken,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl StreamCache {
     pub fn new(api: impl Api) -> Self {
-        let instance = Self {
-            results: Arc::new(Mutex::new(HashMap::new())),
-        };
-        instance.update_in_background(api);
+        Self::with_config(api, StreamCacheConfig::default())
+    }
+
+    /// Like [`StreamCache::new`], but with custom reconnect/backoff and
+    /// re-fetch tunables instead of the defaults.
+    pub fn with_config(api: impl Api, config: StreamCacheConfig) -> Self {
+        let instance = Self::new_with_results(HashMap::new());
+        instance.update_in_background(api, config);
+        instance
+    }
+
+    fn new_with_results(results: HashMap<City, Temperature>) -> Self {
+        Self {
+            results: Arc::new(Mutex::new(results)),
+            senders: Arc::new(Mutex::new(HashMap::new())),
+            city_added: watch::channel(0).0,
+            cancel: CancellationToken::new(),
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Cancels every background task this cache owns and waits for them to
+    /// finish, for deterministic teardown in a larger service. Takes `&self`
+    /// rather than `self` so it composes with `Arc<StreamCache>`, which is
+    /// how this cache is shared with e.g. [`server::router`].
+    pub async fn shutdown(&self) {
+        self.cancel.cancel();
+        let handles = std::mem::take(&mut *self.tasks.lock().expect("poisoned"));
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+
+    fn track(&self, handle: JoinHandle<()>) {
+        self.tasks.lock().expect("poisoned").push(handle);
+    }
+
+    /// Like [`StreamCache::new`], but coalesces `subscribe()` updates into
+    /// batches before touching the cache lock, trading a little latency for
+    /// much less contention under a high-frequency update stream.
+    pub fn with_batching(api: impl Api, max_batch: usize, flush_interval: Duration) -> Self {
+        let instance = Self::new_with_results(HashMap::new());
+        instance.update_in_background_batched(api, max_batch, flush_interval);
         instance
     }
 
@@ -33,41 +152,414 @@ impl StreamCache {
         results.get(key).copied()
     }
 
-    pub fn update_in_background(&self, api: impl Api) {
+    /// Writes the current cache contents to `path` behind a small header
+    /// (cache version + unix timestamp) so a future [`StreamCache::restore`]
+    /// can validate the format before decoding.
+    pub fn snapshot(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let results = self.results.lock().expect("poisoned").clone();
+        Self::write_snapshot(&results, path.as_ref())
+    }
+
+    /// Rebuilds a cache from a snapshot written by [`StreamCache::snapshot`],
+    /// then starts `update_in_background` as usual so live data supersedes
+    /// the restored values as soon as it arrives.
+    pub fn restore(api: impl Api, path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        let mut file = File::open(path)?;
+
+        let mut version = [0u8; 4];
+        file.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != CACHE_VERSION {
+            return Err(SnapshotError::UnsupportedVersion {
+                expected: CACHE_VERSION,
+                found: version,
+            });
+        }
+
+        // the snapshot timestamp isn't needed to rebuild the cache; it only
+        // exists for operators inspecting the file out of band
+        let mut timestamp = [0u8; 8];
+        file.read_exact(&mut timestamp)?;
+
+        let results: HashMap<City, Temperature> =
+            bincode::deserialize_from(&mut file).map_err(SnapshotError::Codec)?;
+
+        let instance = Self::new_with_results(results);
+        instance.update_in_background(api, StreamCacheConfig::default());
+        Ok(instance)
+    }
+
+    /// Periodically writes a snapshot to `path` on the given interval, e.g.
+    /// to keep cold-start recovery data fresh. Failures are logged, not
+    /// fatal, matching the rest of the background tasks.
+    pub fn auto_snapshot(&self, path: impl Into<PathBuf>, interval: Duration) {
+        let path = path.into();
+        let results = self.results.clone();
+        let cancel = self.cancel.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut ticker = time::interval(interval);
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => return,
+                    _ = ticker.tick() => {}
+                }
+
+                let snapshot = results.lock().expect("poisoned").clone();
+                if let Err(err) = Self::write_snapshot(&snapshot, &path) {
+                    println!("Error auto_snapshot(): {}", err);
+                }
+            }
+        });
+        self.track(handle);
+    }
+
+    fn write_snapshot(
+        results: &HashMap<City, Temperature>,
+        path: &Path,
+    ) -> Result<(), SnapshotError> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut file = File::create(path)?;
+        file.write_all(&CACHE_VERSION.to_le_bytes())?;
+        file.write_all(&timestamp.to_le_bytes())?;
+        bincode::serialize_into(&mut file, results).map_err(SnapshotError::Codec)?;
+        Ok(())
+    }
+
+    /// Streams temperature reports for `city`. Per `WatchStream`'s contract,
+    /// the first item is always the most recently recorded temperature,
+    /// followed by every future change. Returns `None` if `city` has not
+    /// appeared in an update yet.
+    pub fn subscribe(&self, city: &str) -> Option<impl Stream<Item = Temperature>> {
+        let senders = self.senders.lock().expect("poisoned");
+        senders
+            .get(city)
+            .map(|tx| WatchStream::new(tx.subscribe()))
+    }
+
+    /// Streams the current temperature of every known city and then its
+    /// later changes, each item tagged with the city it belongs to. Like
+    /// [`StreamCache::subscribe`], a consumer that falls behind sees only the
+    /// latest temperature per city, not every intermediate one. Unlike a one-shot fan-in,
+    /// this keeps watching for cities reported for the first time after the
+    /// call, so it never runs dry just because no city had reported yet.
+    pub fn subscribe_all(&self) -> BoxStream<'static, (City, Temperature)> {
+        let senders = self.senders.clone();
+        let mut city_added = self.city_added.subscribe();
+
+        async_stream::stream! {
+            let mut map: StreamMap<City, WatchStream<Temperature>> = StreamMap::new();
+            let mut known: HashSet<City> = HashSet::new();
+
+            loop {
+                {
+                    let guard = senders.lock().expect("poisoned");
+                    for (city, tx) in guard.iter() {
+                        if known.insert(city.clone()) {
+                            map.insert(city.clone(), WatchStream::new(tx.subscribe()));
+                        }
+                    }
+                }
+
+                tokio::select! {
+                    item = map.next(), if !map.is_empty() => {
+                        if let Some(item) = item {
+                            yield item;
+                        }
+                    }
+                    changed = city_added.changed() => {
+                        if changed.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+        .boxed()
+    }
+
+    pub fn update_in_background(&self, api: impl Api, config: StreamCacheConfig) {
+        self.spawn_background(api, config, None);
+    }
+
+    /// Shared wiring behind [`StreamCache::update_in_background`] and
+    /// [`StreamCache::update_in_background_batched`]: a supervised
+    /// `subscribe()` loop, a one-shot startup `fetch()`, and a periodic
+    /// `fetch()` for drift reconciliation. `batching` is `None` for
+    /// `update_in_background`'s apply-every-item behaviour, or
+    /// `Some((max_batch, flush_interval))` to coalesce updates the same way
+    /// `update_in_background_batched` always has, while still getting the
+    /// resubscribe-with-backoff and periodic-refetch resilience.
+    fn spawn_background(&self, api: impl Api, config: StreamCacheConfig, batching: Option<(usize, Duration)>) {
         let result = self.results.clone();
+        let senders = self.senders.clone();
+        let city_added = self.city_added.clone();
+        let cancel = self.cancel.clone();
         let api = Arc::new(api);
         let api_sub = api.clone();
         let api_fetch = api.clone();
+        let api_refetch = api;
 
-        tokio::spawn(async move {
-            let mut stream = api_sub.subscribe().await;
+        let handle = tokio::spawn(Self::subscribe_supervised(
+            api_sub,
+            result.clone(),
+            senders.clone(),
+            city_added.clone(),
+            config.clone(),
+            cancel.clone(),
+            batching,
+        ));
+        self.track(handle);
 
-            while let Some(update) = stream.next().await {
-                if let Ok((city, temp)) = update {
-                    let mut cache = result.lock().expect("poisoned");
-
-                    cache
-                        .entry(city)
-                        .and_modify(|old_temp| *old_temp = temp)
-                        .or_insert(temp);
+        let handle = tokio::spawn(async move {
+            tokio::select! {
+                _ = cancel.cancelled() => {}
+                fetched = api_fetch.fetch() => match fetched {
+                    Ok(initial_data) => {
+                        let mut cache = result.lock().expect("poisoned");
+                        for (city, temp) in initial_data {
+                            cache.entry(city).or_insert(temp);
+                        }
+                    }
+                    Err(err) => {
+                        println!("Error fetch(): {}", err);
+                    }
                 }
             }
         });
+        self.track(handle);
 
         let result = self.results.clone();
-        tokio::spawn(async move {
-            match api_fetch.fetch().await {
-                Ok(initial_data) => {
-                    let mut cache = result.lock().expect("poisoned");
-                    for (city, temp) in initial_data {
-                        cache.entry(city).or_insert(temp);
+        let cancel = self.cancel.clone();
+        let handle = tokio::spawn(Self::refetch_periodic(
+            api_refetch,
+            result,
+            senders,
+            city_added,
+            config.refetch_interval,
+            cancel,
+        ));
+        self.track(handle);
+    }
+
+    /// Keeps a `subscribe()` stream alive forever: on a terminated stream or
+    /// an item-level error, resubscribes after an exponential backoff (with
+    /// jitter) that resets once updates start flowing again. Exits as soon
+    /// as `cancel` fires. When `batching` is set, updates are coalesced into
+    /// a buffer and flushed on `max_batch` size or `flush_interval`, whether
+    /// or not the underlying stream ever needs to reconnect.
+    async fn subscribe_supervised<A: Api>(
+        api: Arc<A>,
+        result: Arc<Mutex<HashMap<City, Temperature>>>,
+        senders: Arc<Mutex<HashMap<City, watch::Sender<Temperature>>>>,
+        city_added: watch::Sender<u64>,
+        config: StreamCacheConfig,
+        cancel: CancellationToken,
+        batching: Option<(usize, Duration)>,
+    ) {
+        let mut backoff = config.initial_backoff;
+        let mut buffer: HashMap<City, Temperature> = HashMap::new();
+        let mut ticker = batching.map(|(_, flush_interval)| {
+            let mut ticker = time::interval(flush_interval);
+            ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            ticker
+        });
+        if let Some(ticker) = &mut ticker {
+            // the first tick fires immediately; consume it so the first
+            // timed flush comes one full flush_interval after startup
+            ticker.tick().await;
+        }
+
+        loop {
+            // the buffer is always empty here, so there is nothing to flush
+            let mut stream = tokio::select! {
+                _ = cancel.cancelled() => return,
+                stream = api.subscribe() => stream,
+            };
+
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => {
+                        Self::flush_batch(&result, &senders, &city_added, &mut buffer);
+                        return;
+                    }
+                    update = stream.next() => match update {
+                        Some(Ok((city, temp))) => {
+                            backoff = config.initial_backoff;
+                            match batching {
+                                Some((max_batch, _)) => {
+                                    buffer.insert(city, temp);
+                                    if buffer.len() >= max_batch {
+                                        Self::flush_batch(&result, &senders, &city_added, &mut buffer);
+                                    }
+                                }
+                                None => Self::apply_update(&result, &senders, &city_added, city, temp),
+                            }
+                        }
+                        Some(Err(_)) | None => break,
+                    },
+                    _ = Self::tick_or_pending(&mut ticker) => {
+                        Self::flush_batch(&result, &senders, &city_added, &mut buffer);
+                    }
+                }
+            }
+
+            // don't leave a reconnect waiting on data that's already buffered
+            Self::flush_batch(&result, &senders, &city_added, &mut buffer);
+
+            let jitter = Duration::from_millis(rand::thread_rng().gen_range(0..50));
+            tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = time::sleep(backoff + jitter) => {}
+            }
+            backoff = (backoff * 2).min(config.max_backoff);
+        }
+    }
+
+    /// Resolves on the next `ticker` tick, or never if there is no ticker
+    /// (non-batched mode), so it can sit in a `tokio::select!` branch
+    /// unconditionally.
+    async fn tick_or_pending(ticker: &mut Option<time::Interval>) {
+        match ticker {
+            Some(ticker) => {
+                ticker.tick().await;
+            }
+            None => future::pending().await,
+        }
+    }
+
+    /// Reconciles drift by calling `fetch()` on a fixed interval and
+    /// overwriting existing entries with the authoritative snapshot, unlike
+    /// the `or_insert` used for the one-shot startup fetch. Exits as soon as
+    /// `cancel` fires.
+    async fn refetch_periodic<A: Api>(
+        api: Arc<A>,
+        result: Arc<Mutex<HashMap<City, Temperature>>>,
+        senders: Arc<Mutex<HashMap<City, watch::Sender<Temperature>>>>,
+        city_added: watch::Sender<u64>,
+        refetch_interval: Duration,
+        cancel: CancellationToken,
+    ) {
+        let mut ticker = time::interval(refetch_interval);
+        ticker.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = ticker.tick() => {}
+            }
+
+            let fetched = tokio::select! {
+                _ = cancel.cancelled() => return,
+                fetched = api.fetch() => fetched,
+            };
+
+            match fetched {
+                Ok(fetched) => {
+                    for (city, temp) in fetched {
+                        Self::apply_update(&result, &senders, &city_added, city, temp);
                     }
                 }
                 Err(err) => {
                     println!("Error fetch(): {}", err);
                 }
             }
-        });
+        }
+    }
+
+    /// Writes a single `(city, temperature)` update to the cache and notifies
+    /// its watch sender, creating one (and bumping `city_added`) if this is
+    /// the first time `city` has been seen.
+    fn apply_update(
+        result: &Mutex<HashMap<City, Temperature>>,
+        senders: &Mutex<HashMap<City, watch::Sender<Temperature>>>,
+        city_added: &watch::Sender<u64>,
+        city: City,
+        temp: Temperature,
+    ) {
+        {
+            let mut cache = result.lock().expect("poisoned");
+            cache
+                .entry(city.clone())
+                .and_modify(|old_temp| *old_temp = temp)
+                .or_insert(temp);
+        }
+
+        let mut senders = senders.lock().expect("poisoned");
+        if let Some(tx) = senders.get(&city) {
+            tx.send_if_modified(|old| {
+                if *old != temp {
+                    *old = temp;
+                    true
+                } else {
+                    false
+                }
+            });
+        } else {
+            senders.insert(city, watch::channel(temp).0);
+            city_added.send_modify(|v| *v = v.wrapping_add(1));
+        }
+    }
+
+    /// Like [`StreamCache::update_in_background`], but coalesces updates
+    /// into batches of up to `max_batch` (or `flush_interval`, whichever
+    /// comes first) before touching the cache lock. Goes through the same
+    /// supervised reconnect-with-backoff and periodic-refetch machinery, so
+    /// a dropped `subscribe()` stream doesn't freeze this cache either.
+    fn update_in_background_batched(&self, api: impl Api, max_batch: usize, flush_interval: Duration) {
+        self.spawn_background(
+            api,
+            StreamCacheConfig::default(),
+            Some((max_batch, flush_interval)),
+        );
+    }
+
+    /// Applies a batch of coalesced `(city, temperature)` updates to the
+    /// cache and the per-city watch senders with a single lock acquisition
+    /// each, then clears the batch.
+    fn flush_batch(
+        result: &Mutex<HashMap<City, Temperature>>,
+        senders: &Mutex<HashMap<City, watch::Sender<Temperature>>>,
+        city_added: &watch::Sender<u64>,
+        buffer: &mut HashMap<City, Temperature>,
+    ) {
+        if buffer.is_empty() {
+            return;
+        }
+
+        {
+            let mut cache = result.lock().expect("poisoned");
+            for (city, temp) in buffer.iter() {
+                cache.insert(city.clone(), *temp);
+            }
+        }
+
+        let mut senders = senders.lock().expect("poisoned");
+        for (city, temp) in buffer.drain() {
+            if let Some(tx) = senders.get(&city) {
+                tx.send_if_modified(|old| {
+                    if *old != temp {
+                        *old = temp;
+                        true
+                    } else {
+                        false
+                    }
+                });
+            } else {
+                senders.insert(city, watch::channel(temp).0);
+                city_added.send_modify(|v| *v = v.wrapping_add(1));
+            }
+        }
+    }
+}
+
+impl Drop for StreamCache {
+    fn drop(&mut self) {
+        self.cancel.cancel();
     }
 }
 
@@ -125,4 +617,191 @@ mod tests {
         assert_eq!(cache.get("London"), Some(27));
         assert_eq!(cache.get("Seoul"), Some(32));
     }
+
+    #[tokio::test]
+    async fn subscribe_yields_the_current_value_for_a_known_city() {
+        let cache = StreamCache::new(TestApi::default());
+
+        // give the subscribe task a moment to observe the first updates
+        time::sleep(Duration::from_millis(200)).await;
+
+        assert!(cache.subscribe("Tokyo").is_none());
+
+        let mut london = cache.subscribe("London").expect("London was reported");
+        assert_eq!(london.next().await, Some(27));
+    }
+
+    #[tokio::test]
+    async fn subscribe_all_picks_up_a_city_reported_after_the_call() {
+        let cache = StreamCache::new(TestApi::default());
+
+        // subscribe before anything has been reported; a naive one-shot
+        // fan-in would already be exhausted by the time updates arrive
+        let mut all = cache.subscribe_all();
+
+        let (city, temp) = tokio::time::timeout(Duration::from_secs(1), all.next())
+            .await
+            .expect("subscribe_all should not run dry before any city is known")
+            .expect("stream should yield an update");
+        assert_eq!(temp, cache.get(&city).expect("reported city is cached"));
+    }
+
+    #[derive(Default)]
+    struct BurstApi {
+        signal: Arc<Notify>,
+    }
+
+    #[async_trait]
+    impl Api for BurstApi {
+        async fn fetch(&self) -> Result<HashMap<City, Temperature>, String> {
+            self.signal.notified().await;
+            Ok(hashmap! {})
+        }
+        async fn subscribe(&self) -> BoxStream<Result<(City, Temperature), String>> {
+            let updates = (0..100).map(|temp| Ok(("Paris".to_string(), temp)));
+
+            select(
+                futures::stream::iter(updates),
+                async {
+                    self.signal.notify_one();
+                    future::pending().await
+                }
+                .into_stream(),
+            )
+            .boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn with_batching_coalesces_a_burst_into_the_last_value() {
+        let cache = StreamCache::with_batching(BurstApi::default(), 1000, Duration::from_millis(50));
+
+        time::sleep(Duration::from_millis(300)).await;
+
+        assert_eq!(cache.get("Paris"), Some(99));
+    }
+
+    struct NeverApi;
+
+    #[async_trait]
+    impl Api for NeverApi {
+        async fn fetch(&self) -> Result<HashMap<City, Temperature>, String> {
+            future::pending().await
+        }
+        async fn subscribe(&self) -> BoxStream<Result<(City, Temperature), String>> {
+            futures::stream::pending().boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn snapshot_roundtrips_through_restore() {
+        let cache = StreamCache::new(TestApi::default());
+        time::sleep(Duration::from_millis(1000)).await;
+
+        let path = std::env::temp_dir().join("stream_cache_snapshot_roundtrip_test.bin");
+        cache.snapshot(&path).expect("snapshot should succeed");
+
+        let restored = StreamCache::restore(NeverApi, &path).expect("restore should succeed");
+        assert_eq!(restored.get("Rabat"), Some(29));
+        assert_eq!(restored.get("London"), Some(27));
+        assert_eq!(restored.get("Seoul"), Some(32));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn auto_snapshot_writes_on_the_interval_and_stops_after_shutdown() {
+        let cache = StreamCache::new(TestApi::default());
+        time::sleep(Duration::from_millis(200)).await;
+
+        let path = std::env::temp_dir().join("stream_cache_auto_snapshot_test.bin");
+        std::fs::remove_file(&path).ok();
+        cache.auto_snapshot(path.clone(), Duration::from_millis(50));
+
+        time::sleep(Duration::from_millis(200)).await;
+        assert!(path.exists(), "auto_snapshot should have written a file by now");
+
+        cache.shutdown().await;
+        std::fs::remove_file(&path).ok();
+
+        // the background task observed cancellation and stopped ticking, so
+        // no new snapshot appears after shutdown
+        time::sleep(Duration::from_millis(200)).await;
+        assert!(
+            !path.exists(),
+            "auto_snapshot should not write again after shutdown"
+        );
+    }
+
+    #[derive(Default)]
+    struct FlakyApi {
+        signal: Arc<Notify>,
+        attempts: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Api for FlakyApi {
+        async fn fetch(&self) -> Result<HashMap<City, Temperature>, String> {
+            future::pending().await
+        }
+        async fn subscribe(&self) -> BoxStream<Result<(City, Temperature), String>> {
+            use std::sync::atomic::Ordering;
+
+            self.signal.notify_one();
+
+            if self.attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                // first subscription drops immediately, forcing a reconnect
+                futures::stream::empty().boxed()
+            } else {
+                futures::stream::iter(vec![Ok(("Lima".to_string(), 18))]).boxed()
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn resubscribes_after_the_stream_terminates() {
+        let config = StreamCacheConfig {
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+            ..StreamCacheConfig::default()
+        };
+        let cache = StreamCache::with_config(FlakyApi::default(), config);
+
+        time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(cache.get("Lima"), Some(18));
+    }
+
+    #[tokio::test]
+    async fn with_batching_resubscribes_after_the_stream_terminates() {
+        // with_batching used to have no resubscribe/backoff loop at all, so a
+        // terminated stream left the cache frozen forever; it now shares
+        // subscribe_supervised with update_in_background.
+        let cache = StreamCache::with_batching(FlakyApi::default(), 1, Duration::from_millis(50));
+
+        time::sleep(Duration::from_millis(300)).await;
+
+        assert_eq!(cache.get("Lima"), Some(18));
+    }
+
+    #[tokio::test]
+    async fn restore_rejects_a_bad_version_header() {
+        let path = std::env::temp_dir().join("stream_cache_snapshot_bad_version_test.bin");
+        std::fs::write(&path, 0xFFFFFFFFu32.to_le_bytes()).expect("write should succeed");
+
+        let err = StreamCache::restore(NeverApi, &path).expect_err("version mismatch");
+        assert!(matches!(err, SnapshotError::UnsupportedVersion { .. }));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn shutdown_joins_background_tasks() {
+        let cache = StreamCache::new(TestApi::default());
+        time::sleep(Duration::from_millis(200)).await;
+
+        // completes rather than hanging forever: the background tasks
+        // observe the cancellation and return
+        cache.shutdown().await;
+    }
 }
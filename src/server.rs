@@ -0,0 +1,152 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive},
+        IntoResponse, Sse,
+    },
+    routing::get,
+    Json, Router,
+};
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+
+use crate::{City, StreamCache, Temperature};
+
+#[derive(Serialize)]
+struct TemperatureUpdate {
+    city: City,
+    temperature: Temperature,
+}
+
+/// Mounts `GET /temp/:city` and `GET /events` over a shared [`StreamCache`].
+pub fn router(cache: Arc<StreamCache>) -> Router {
+    Router::new()
+        .route("/temp/:city", get(get_temperature))
+        .route("/events", get(stream_events))
+        .with_state(cache)
+}
+
+async fn get_temperature(
+    State(cache): State<Arc<StreamCache>>,
+    Path(city): Path<City>,
+) -> impl IntoResponse {
+    match cache.get(&city) {
+        Some(temperature) => Json(TemperatureUpdate { city, temperature }).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Pushes every cache update to the client as an SSE event. A client that
+/// can't keep up simply sees the latest temperature next time it catches up
+/// (the underlying `watch` channel only retains the latest value per city),
+/// so a slow client is dropped behind rather than backpressuring the shared
+/// update tasks.
+async fn stream_events(
+    State(cache): State<Arc<StreamCache>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    Sse::new(temperature_events(cache)).keep_alive(KeepAlive::default())
+}
+
+/// The `/events` body as a plain `Stream`, split out from [`stream_events`]
+/// so it can be driven directly in tests without going through axum's `Sse`
+/// wrapper. Relies on `StreamCache::subscribe_all` to keep delivering
+/// events even if no city had reported anything yet when the client
+/// connected.
+fn temperature_events(
+    cache: Arc<StreamCache>,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    async_stream::stream! {
+        let mut updates = cache.subscribe_all();
+        while let Some((city, temperature)) = updates.next().await {
+            yield Ok(Event::default()
+                .json_data(TemperatureUpdate { city, temperature })
+                .unwrap_or_default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use async_trait::async_trait;
+    use futures::{future, stream::select, FutureExt};
+    use tokio::{sync::Notify, time};
+
+    use super::*;
+    use crate::Api;
+
+    #[derive(Default)]
+    struct SlowFetchApi {
+        signal: Arc<Notify>,
+    }
+
+    #[async_trait]
+    impl Api for SlowFetchApi {
+        async fn fetch(&self) -> Result<HashMap<City, Temperature>, String> {
+            // fetch never resolves in time for the test: /events has to work
+            // even though no city has been reported through it yet
+            self.signal.notified().await;
+            future::pending().await
+        }
+        async fn subscribe(&self) -> futures::stream::BoxStream<Result<(City, Temperature), String>> {
+            let results = vec![Ok(("Accra".to_string(), 33))];
+
+            select(
+                futures::stream::iter(results),
+                async {
+                    self.signal.notify_one();
+                    future::pending().await
+                }
+                .into_stream(),
+            )
+            .boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn events_stream_does_not_run_dry_before_the_first_city_is_known() {
+        let cache = Arc::new(StreamCache::new(SlowFetchApi::default()));
+        let mut events = Box::pin(temperature_events(cache));
+
+        let event = time::timeout(Duration::from_secs(1), events.next())
+            .await
+            .expect("/events should not close before a city is ever reported")
+            .expect("stream should yield an event")
+            .expect("event should be Ok");
+
+        assert!(format!("{:?}", event).contains("Accra"));
+    }
+
+    #[tokio::test]
+    async fn get_temperature_returns_the_cached_value_for_a_known_city() {
+        let cache = Arc::new(StreamCache::new(SlowFetchApi::default()));
+        time::sleep(Duration::from_millis(50)).await;
+
+        let response =
+            get_temperature(State(cache), Path("Accra".to_string())).await.into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should be readable");
+        let parsed: serde_json::Value = serde_json::from_slice(&body).expect("body should be valid json");
+        assert_eq!(parsed["city"], "Accra");
+        assert_eq!(parsed["temperature"], 33);
+    }
+
+    #[tokio::test]
+    async fn get_temperature_404s_for_an_unknown_city() {
+        let cache = Arc::new(StreamCache::new(SlowFetchApi::default()));
+
+        let response =
+            get_temperature(State(cache), Path("Nowhere".to_string())).await.into_response();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}